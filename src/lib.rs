@@ -11,8 +11,31 @@
 //! competing writes (to avoid interleaved writes) and partial reads are
 //! not an issue. Types that can be read in a single instruction, like small
 //! integers, will likely not suffer from these issues on some platforms.
+//!
+//! The crate is `no_std` and only requires `alloc`, so it can be used in
+//! embedded and kernel contexts, which are the natural home for a racy,
+//! lock-free-ish shared buffer. `from_vec`/`into_inner` are gated behind the
+//! `std` feature, since they are convenience conversions to/from `std`'s
+//! `Vec` rather than part of the core storage model.
+
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+use core::ptr;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
 
-use std::ptr;
+pub mod ring;
+pub use ring::{RaceRing, Reader};
+
+pub mod share;
+pub use share::{ReaderFactory, SharedReader, Writer};
 
 /// Racy buffer
 ///
@@ -20,39 +43,142 @@ use std::ptr;
 ///
 /// Guarantees almost nothing else, especially that values stored or loaded
 /// are actually valid values for type `T`.
-pub struct RaceBuf<T>(Vec<T>);
+pub struct RaceBuf<T> {
+    ptr: *mut T,
+    len: usize,
+    cap: usize,
+    owned: bool,
+}
+
+// The volatile get/set operations already make concurrent access from
+// multiple threads "safe" in the sense this crate promises (crash-free,
+// not tear-free), so there is no reason to forbid crossing a thread
+// boundary the way an ordinary raw-pointer-holding type would.
+unsafe impl<T: Send> Send for RaceBuf<T> {}
+unsafe impl<T: Send> Sync for RaceBuf<T> {}
 
 impl<T: Clone + Copy> RaceBuf<T> {
     /// Create a new buffer, initialized with `value`.
     #[inline]
     pub fn new_with_value(size: usize, value: T) -> RaceBuf<T> {
-        let mut v = Vec::with_capacity(size);
-        v.resize(size, value);
-        RaceBuf(v)
+        let ptr = Self::alloc_raw(size);
+        for i in 0..size {
+            unsafe { ptr::write(ptr.add(i), value) };
+        }
+        RaceBuf {
+            ptr,
+            len: size,
+            cap: size,
+            owned: true,
+        }
+    }
+
+    /// Create a new buffer of `size` elements without initializing them.
+    ///
+    /// Unlike `new`/`new_with_value`, this skips the per-element fill, so
+    /// construction is a single allocation regardless of `size`. Reading a
+    /// slot before it has been `set` is still defined not to crash, but
+    /// the value it yields is arbitrary and possibly not a valid `T` at
+    /// all — no different from the guarantee `get` already gives for any
+    /// `RaceBuf`.
+    #[inline]
+    pub fn new_uninit(size: usize) -> RaceBuf<T> {
+        let ptr = Self::alloc_raw(size);
+        RaceBuf {
+            ptr,
+            len: size,
+            cap: size,
+            owned: true,
+        }
+    }
+
+    /// Allocate `size` elements of raw, uninitialized storage.
+    fn alloc_raw(size: usize) -> *mut T {
+        if size == 0 || core::mem::size_of::<T>() == 0 {
+            return core::ptr::NonNull::dangling().as_ptr();
+        }
+        let layout = Layout::array::<T>(size).expect("capacity overflow");
+        let ptr = unsafe { alloc(layout) } as *mut T;
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+        ptr
     }
 
     /// Return a pointer to the first element in the buffer
     #[inline]
     pub fn as_ptr(&self) -> *const RaceBuf<T> {
-        self.0.as_ptr() as *const RaceBuf<T>
+        self.ptr as *const RaceBuf<T>
+    }
+
+    /// Return a mutable pointer to the first element in the buffer
+    ///
+    /// Hands the buffer's address back out, e.g. to pass to
+    /// `from_raw_parts` elsewhere, which is what wraps memory the caller
+    /// already owns (an MMIO region, a pre-reserved DMA pool, a mapped
+    /// page) as a `RaceBuf`.
+    #[inline]
+    pub fn as_mut_ptr(&self) -> *mut T {
+        self.ptr
     }
 
     /// Create a new buffer from existing vector
+    #[cfg(feature = "std")]
     #[inline]
-    pub fn from_vec(vec: Vec<T>) -> RaceBuf<T> {
-        RaceBuf(vec)
+    pub fn from_vec(mut vec: Vec<T>) -> RaceBuf<T> {
+        vec.shrink_to_fit();
+        let len = vec.len();
+        let cap = vec.capacity();
+        let ptr = vec.as_mut_ptr();
+        core::mem::forget(vec);
+        RaceBuf {
+            ptr,
+            len,
+            cap,
+            owned: true,
+        }
     }
 
     /// Extra inner buffer from RaceBuf
+    #[cfg(feature = "std")]
     #[inline]
     pub fn into_inner(self) -> Vec<T> {
-        self.0
+        let vec = unsafe { Vec::from_raw_parts(self.ptr, self.len, self.cap) };
+        core::mem::forget(self);
+        vec
+    }
+
+    /// Wrap memory the caller already owns (an MMIO region, a
+    /// pre-reserved DMA pool, a mapped page) as a `RaceBuf`.
+    ///
+    /// The returned `RaceBuf` does not take ownership and will not
+    /// deallocate `ptr` when dropped.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for volatile reads and writes of `len`
+    /// elements of `T` for as long as the returned `RaceBuf` is alive, and
+    /// nothing else may free or move that memory out from under it.
+    #[inline]
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize) -> RaceBuf<T> {
+        RaceBuf {
+            ptr,
+            len,
+            cap: len,
+            owned: false,
+        }
     }
 
     /// Get buffer length
     #[inline]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.len
+    }
+
+    /// Returns `true` if the buffer has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     /// Retrieve value stored at index
@@ -60,7 +186,7 @@ impl<T: Clone + Copy> RaceBuf<T> {
     /// Will return `None` if the index is out-of-bounds.
     #[inline(always)]
     pub fn get(&self, idx: usize) -> Option<T> {
-        if idx >= self.0.len() {
+        if idx >= self.len {
             None
         } else {
             Some(unsafe { self.get_unchecked(idx) })
@@ -68,10 +194,14 @@ impl<T: Clone + Copy> RaceBuf<T> {
     }
 
     /// Retrieve value stored at index without bounds checking
+    ///
+    /// # Safety
+    ///
+    /// `idx` must be less than `self.len()`.
     #[inline(always)]
     pub unsafe fn get_unchecked(&self, idx: usize) -> T {
         // unsafe: bounds are not checked, pointer deref
-        ptr::read_volatile(self.0.as_ptr().offset(idx as isize))
+        ptr::read_volatile(self.ptr.add(idx))
     }
 
     /// Set value at index
@@ -79,9 +209,7 @@ impl<T: Clone + Copy> RaceBuf<T> {
     /// If `idx` is out-of-bounds, `set` will have no effect.
     #[inline(always)]
     pub fn set(&self, idx: usize, value: T) {
-        if idx >= self.0.len() {
-            return;
-        } else {
+        if idx < self.len {
             unsafe { self.set_unchecked(idx, value) }
         }
     }
@@ -90,10 +218,74 @@ impl<T: Clone + Copy> RaceBuf<T> {
     ///
     /// Will definately cause undefined behaviour if `idx` is not within
     /// bounds.
+    ///
+    /// # Safety
+    ///
+    /// `idx` must be less than `self.len()`.
     #[inline(always)]
     pub unsafe fn set_unchecked(&self, idx: usize, value: T) {
         // unsafe: bounds are not checked, pointer deref, *const T to *mut T
-        ptr::write_volatile(self.0.as_ptr().offset(idx as isize) as *mut T, value)
+        ptr::write_volatile(self.ptr.add(idx), value)
+    }
+
+    /// Read a range of elements starting at `start` into `dst`, volatile
+    /// element by element.
+    ///
+    /// Clamps to the buffer's bounds; returns the number of elements
+    /// actually transferred, which may be less than `dst.len()` if the
+    /// buffer does not have that many elements left from `start`.
+    #[inline]
+    pub fn read_into(&self, start: usize, dst: &mut [T]) -> usize {
+        if start >= self.len {
+            return 0;
+        }
+        let n = dst.len().min(self.len - start);
+        for (i, slot) in dst.iter_mut().enumerate().take(n) {
+            *slot = unsafe { self.get_unchecked(start + i) };
+        }
+        n
+    }
+
+    /// Write a range of elements from `src` starting at `start`, volatile
+    /// element by element.
+    ///
+    /// Clamps to the buffer's bounds; returns the number of elements
+    /// actually transferred, which may be less than `src.len()` if the
+    /// buffer does not have that many elements left from `start`.
+    #[inline]
+    pub fn write_from(&self, start: usize, src: &[T]) -> usize {
+        if start >= self.len {
+            return 0;
+        }
+        let n = src.len().min(self.len - start);
+        for (i, value) in src.iter().enumerate().take(n) {
+            unsafe { self.set_unchecked(start + i, *value) };
+        }
+        n
+    }
+}
+
+impl RaceBuf<u8> {
+    /// Read a range of bytes starting at `start` into `dst`, volatile.
+    ///
+    /// Clamps to the buffer's bounds and returns the number of bytes
+    /// actually transferred. Useful when a `RaceBuf<u8>` backs memory that
+    /// is DMA'd or shared with a device, where every access must stay
+    /// volatile.
+    #[inline]
+    pub fn read_volatile(&self, start: usize, dst: &mut [u8]) -> usize {
+        self.read_into(start, dst)
+    }
+
+    /// Write a range of bytes from `src` starting at `start`, volatile.
+    ///
+    /// Clamps to the buffer's bounds and returns the number of bytes
+    /// actually transferred. Useful when a `RaceBuf<u8>` backs memory that
+    /// is DMA'd or shared with a device, where every access must stay
+    /// volatile.
+    #[inline]
+    pub fn write_volatile(&self, start: usize, src: &[u8]) -> usize {
+        self.write_from(start, src)
     }
 }
 
@@ -104,3 +296,82 @@ impl<T: Clone + Copy + Default> RaceBuf<T> {
         RaceBuf::new_with_value(size, T::default())
     }
 }
+
+impl<T> Drop for RaceBuf<T> {
+    fn drop(&mut self) {
+        if !self.owned {
+            return;
+        }
+        unsafe {
+            for i in 0..self.len {
+                ptr::drop_in_place(self.ptr.add(i));
+            }
+        }
+        if self.cap == 0 || core::mem::size_of::<T>() == 0 {
+            return;
+        }
+        unsafe {
+            let layout = Layout::array::<T>(self.cap).expect("capacity overflow");
+            dealloc(self.ptr as *mut u8, layout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alloc_and_dealloc_round_trips_through_get() {
+        let buf: RaceBuf<u32> = RaceBuf::new_with_value(4, 7);
+        assert_eq!(buf.get(0), Some(7));
+        assert_eq!(buf.get(3), Some(7));
+        assert_eq!(buf.get(4), None);
+        drop(buf);
+    }
+
+    #[test]
+    fn new_uninit_is_readable_after_being_written() {
+        let buf: RaceBuf<u32> = RaceBuf::new_uninit(4);
+        buf.set(0, 42);
+        assert_eq!(buf.get(0), Some(42));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_vec_into_inner_round_trips() {
+        let original = std::vec![1u32, 2, 3, 4, 5];
+        let buf = RaceBuf::from_vec(original.clone());
+        assert_eq!(buf.len(), original.len());
+        let back = buf.into_inner();
+        assert_eq!(back, original);
+    }
+
+    #[test]
+    fn read_into_clamps_at_the_boundary() {
+        let buf: RaceBuf<u32> = RaceBuf::new_with_value(4, 9);
+        let mut dst = [0u32; 8];
+        let n = buf.read_into(2, &mut dst);
+        assert_eq!(n, 2);
+        assert_eq!(&dst[..2], &[9, 9]);
+    }
+
+    #[test]
+    fn write_from_clamps_at_the_boundary() {
+        let buf: RaceBuf<u32> = RaceBuf::new_with_value(4, 0);
+        let src = [1u32, 2, 3, 4, 5, 6];
+        let n = buf.write_from(2, &src);
+        assert_eq!(n, 2);
+        assert_eq!(buf.get(2), Some(1));
+        assert_eq!(buf.get(3), Some(2));
+    }
+
+    #[test]
+    fn zero_sized_type_round_trips_without_allocating() {
+        let buf: RaceBuf<()> = RaceBuf::new_with_value(5, ());
+        assert_eq!(buf.len(), 5);
+        assert_eq!(buf.get(4), Some(()));
+        assert_eq!(buf.get(5), None);
+        drop(buf);
+    }
+}
@@ -0,0 +1,128 @@
+//! A racy single-producer / multiple-consumer ring buffer
+//!
+//! `RaceRing` is a chunked queue layered on `RaceBuf`: a single producer
+//! advances a write head in fixed-size chunks, and any number of `Reader`
+//! handles track their own read cursor over the same backing buffer.
+//!
+//! As with `RaceBuf`, this only guarantees crash-freedom, not
+//! tear-freedom: it is on the caller to schedule producer and readers so
+//! that a chunk is never written and read at the same time.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::RaceBuf;
+
+/// Racy SPMC ring buffer, backed by a power-of-two `RaceBuf`.
+///
+/// Data is written and read in fixed-size chunks of `N` elements at a time,
+/// so the compiler can elide per-element bounds checks and vectorize the
+/// copy. Wrap-around uses `idx & mask` instead of a modulo.
+///
+/// As with `RaceBuf`, correctness depends on the caller ensuring a reader
+/// and the writer never touch the same chunk concurrently; this type only
+/// promises not to crash, not that reads will be torn-free.
+pub struct RaceRing<T, const N: usize> {
+    buf: RaceBuf<T>,
+    mask: usize,
+    write_head: AtomicUsize,
+}
+
+impl<T: Clone + Copy + Default, const N: usize> RaceRing<T, N> {
+    /// Create a new ring buffer with the given capacity.
+    ///
+    /// `capacity` must be a power of two and a multiple of `N`.
+    pub fn new(capacity: usize) -> RaceRing<T, N> {
+        assert!(N > 0, "chunk size must be non-zero");
+        assert!(capacity.is_power_of_two(), "capacity must be a power of two");
+        assert!(
+            capacity.is_multiple_of(N),
+            "capacity must be a multiple of chunk size"
+        );
+        RaceRing {
+            buf: RaceBuf::new(capacity),
+            mask: capacity - 1,
+            write_head: AtomicUsize::new(0),
+        }
+    }
+
+    /// Push a chunk of `N` elements, advancing the write head.
+    ///
+    /// The caller is responsible for ensuring that no reader is still
+    /// reading the chunk this write is about to overwrite.
+    pub fn push_chunk(&self, chunk: &[T; N]) {
+        let head = self.write_head.load(Ordering::Relaxed);
+        for (i, value) in chunk.iter().enumerate() {
+            let idx = (head + i) & self.mask;
+            unsafe { self.buf.set_unchecked(idx, *value) };
+        }
+        self.write_head.store(head + N, Ordering::Relaxed);
+    }
+
+    /// Create a new `Reader` over this ring, starting at the current head.
+    pub fn reader(&self) -> Reader<'_, T, N> {
+        Reader {
+            ring: self,
+            read_cursor: AtomicUsize::new(self.write_head.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// A lightweight handle into a `RaceRing`, carrying its own read cursor.
+///
+/// Multiple `Reader`s may exist for the same ring; each tracks its own
+/// position independently of the others and of the writer.
+pub struct Reader<'a, T, const N: usize> {
+    ring: &'a RaceRing<T, N>,
+    read_cursor: AtomicUsize,
+}
+
+impl<'a, T: Clone + Copy + Default, const N: usize> Reader<'a, T, N> {
+    /// Read the next chunk of `N` elements and advance this reader's cursor.
+    ///
+    /// The values returned are only meaningful if the writer has not
+    /// overwritten them since; this is on the caller to guarantee.
+    pub fn read_chunk(&self) -> [T; N] {
+        let cursor = self.read_cursor.load(Ordering::Relaxed);
+        let mut out = [T::default(); N];
+        for (i, slot) in out.iter_mut().enumerate() {
+            let idx = (cursor + i) & self.ring.mask;
+            *slot = unsafe { self.ring.buf.get_unchecked(idx) };
+        }
+        self.read_cursor.store(cursor + N, Ordering::Relaxed);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_a_pushed_chunk() {
+        let ring: RaceRing<u32, 4> = RaceRing::new(16);
+        let reader = ring.reader();
+        ring.push_chunk(&[1, 2, 3, 4]);
+        assert_eq!(reader.read_chunk(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn reader_only_sees_chunks_pushed_after_it_was_created() {
+        let ring: RaceRing<u32, 4> = RaceRing::new(16);
+        ring.push_chunk(&[1, 2, 3, 4]);
+        let reader = ring.reader();
+        ring.push_chunk(&[5, 6, 7, 8]);
+        assert_eq!(reader.read_chunk(), [5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn wraps_around_the_backing_buffer() {
+        let ring: RaceRing<u32, 4> = RaceRing::new(8);
+        let reader = ring.reader();
+        ring.push_chunk(&[1, 2, 3, 4]);
+        ring.push_chunk(&[5, 6, 7, 8]);
+        assert_eq!(reader.read_chunk(), [1, 2, 3, 4]);
+        assert_eq!(reader.read_chunk(), [5, 6, 7, 8]);
+        ring.push_chunk(&[9, 10, 11, 12]);
+        assert_eq!(reader.read_chunk(), [9, 10, 11, 12]);
+    }
+}
@@ -0,0 +1,177 @@
+//! Splitting a `RaceBuf` into a single writer and many racy readers
+//!
+//! `split` wraps a `RaceBuf` in an `Arc` and hands back a `Writer` and a
+//! `ReaderFactory`. The factory clones cheap [`SharedReader`] handles, so
+//! the one-writer-many-readers-across-threads shape this type is meant
+//! for doesn't need its own `Arc` plus `unsafe impl Sync` newtype at every
+//! call site.
+
+use alloc::sync::Arc;
+
+use crate::RaceBuf;
+
+impl<T> RaceBuf<T> {
+    /// Split this buffer into a single `Writer` and a factory for cheap,
+    /// cloneable `SharedReader` handles, sharing the buffer via an `Arc`.
+    #[inline]
+    pub fn split(self) -> (Writer<T>, ReaderFactory<T>) {
+        let buf = Arc::new(self);
+        (
+            Writer {
+                buf: Arc::clone(&buf),
+            },
+            ReaderFactory { buf },
+        )
+    }
+}
+
+/// The write half of a split `RaceBuf`.
+pub struct Writer<T> {
+    buf: Arc<RaceBuf<T>>,
+}
+
+impl<T: Clone + Copy> Writer<T> {
+    /// Get buffer length
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if the buffer has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buf.len() == 0
+    }
+
+    /// Set value at index
+    ///
+    /// If `idx` is out-of-bounds, `set` will have no effect.
+    #[inline]
+    pub fn set(&self, idx: usize, value: T) {
+        self.buf.set(idx, value)
+    }
+
+    /// Write a range of elements from `src` starting at `start`, volatile
+    /// element by element.
+    #[inline]
+    pub fn write_from(&self, start: usize, src: &[T]) -> usize {
+        self.buf.write_from(start, src)
+    }
+}
+
+/// Hands out cheap, cloneable [`SharedReader`] handles over a shared `RaceBuf`.
+pub struct ReaderFactory<T> {
+    buf: Arc<RaceBuf<T>>,
+}
+
+impl<T> ReaderFactory<T> {
+    /// Create a new `SharedReader` handle over the shared buffer.
+    #[inline]
+    pub fn reader(&self) -> SharedReader<T> {
+        SharedReader {
+            buf: Arc::clone(&self.buf),
+        }
+    }
+}
+
+impl<T> Clone for ReaderFactory<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        ReaderFactory {
+            buf: Arc::clone(&self.buf),
+        }
+    }
+}
+
+/// A cheap, cloneable racy reader handle over a shared `RaceBuf`.
+pub struct SharedReader<T> {
+    buf: Arc<RaceBuf<T>>,
+}
+
+impl<T: Clone + Copy> SharedReader<T> {
+    /// Get buffer length
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if the buffer has no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buf.len() == 0
+    }
+
+    /// Retrieve value stored at index
+    ///
+    /// Will return `None` if the index is out-of-bounds.
+    #[inline]
+    pub fn get(&self, idx: usize) -> Option<T> {
+        self.buf.get(idx)
+    }
+
+    /// Read a range of elements starting at `start` into `dst`, volatile
+    /// element by element.
+    #[inline]
+    pub fn read_into(&self, start: usize, dst: &mut [T]) -> usize {
+        self.buf.read_into(start, dst)
+    }
+}
+
+impl<T> Clone for SharedReader<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        SharedReader {
+            buf: Arc::clone(&self.buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RaceBuf;
+
+    #[test]
+    fn set_through_writer_is_visible_via_shared_reader() {
+        let (writer, readers) = RaceBuf::new_with_value(4, 0u32).split();
+        let reader = readers.reader();
+        writer.set(1, 42);
+        assert_eq!(reader.get(1), Some(42));
+    }
+
+    #[test]
+    fn write_from_through_writer_is_visible_via_read_into() {
+        let (writer, readers) = RaceBuf::new_with_value(4, 0u32).split();
+        let reader = readers.reader();
+        writer.write_from(0, &[1, 2, 3, 4]);
+        let mut dst = [0u32; 4];
+        let n = reader.read_into(0, &mut dst);
+        assert_eq!(n, 4);
+        assert_eq!(dst, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn is_empty_reflects_buffer_length() {
+        let (writer, readers) = RaceBuf::new_with_value(0, 0u32).split();
+        assert!(writer.is_empty());
+        assert!(readers.reader().is_empty());
+
+        let (writer, readers) = RaceBuf::new_with_value(4, 0u32).split();
+        assert!(!writer.is_empty());
+        assert!(!readers.reader().is_empty());
+    }
+
+    #[test]
+    fn reader_factory_and_shared_reader_clones_share_the_buffer() {
+        let (writer, readers) = RaceBuf::new_with_value(4, 0u32).split();
+        let readers2 = readers.clone();
+        writer.set(0, 7);
+        assert_eq!(readers.reader().get(0), Some(7));
+        assert_eq!(readers2.reader().get(0), Some(7));
+
+        let reader = readers.reader();
+        let reader2 = reader.clone();
+        writer.set(0, 9);
+        assert_eq!(reader.get(0), Some(9));
+        assert_eq!(reader2.get(0), Some(9));
+    }
+}